@@ -1,22 +1,525 @@
 //! # log_filter_parse
 use std::{borrow::Cow, collections::HashMap};
 
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+/// Splits `input` into the directive list and an optional trailing
+/// `/regex` suffix (mirroring `env_logger`'s `spec/regex` form), skipping
+/// over `{...}` field-predicate blocks so a `/` in a field value (e.g. a
+/// path) isn't mistaken for the message-pattern separator
+fn split_directives_and_message(input: &str) -> (&str, Option<&str>) {
+    let mut depth = 0usize;
+    for (i, ch) in input.char_indices() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '/' if depth == 0 => return (&input[..i], Some(&input[i + 1..])),
+            _ => {}
+        }
+    }
+    (input, None)
+}
+
+/// A `target{key=value,...}=level` directive's key-value predicates
+#[cfg(feature = "kv")]
+#[derive(Debug, Clone)]
+struct FieldDirective {
+    target: String,
+    fields: Vec<(String, String)>,
+}
+
+#[cfg(feature = "kv")]
+impl FieldDirective {
+    /// A directive only matches a record when every one of its fields is
+    /// present in the record's structured key-values and equal to the
+    /// expected value
+    fn matches(&self, record: &log::Record<'_>) -> bool {
+        let source = record.key_values();
+        self.fields.iter().all(|(key, expected)| {
+            source
+                .get(log::kv::Key::from_str(key))
+                .is_some_and(|value| kv_value_matches(&value, expected))
+        })
+    }
+}
+
+/// Compares a `log::kv::Value` against the expected string from a
+/// directive, comparing structurally for bools/integers when the
+/// expected value parses as one, falling back to string comparison
+#[cfg(feature = "kv")]
+fn kv_value_matches(value: &log::kv::Value<'_>, expected: &str) -> bool {
+    if let (Some(actual), Ok(expected)) = (value.to_bool(), expected.parse::<bool>()) {
+        return actual == expected;
+    }
+    if let (Some(actual), Ok(expected)) = (value.to_i64(), expected.parse::<i64>()) {
+        return actual == expected;
+    }
+    value.to_string() == expected
+}
+
+/// Strips a `{key=value,...}` field-predicate block out of a single
+/// directive, returning the bare `target=level` (or `target`) form
+/// alongside the predicates it carried, if any
+fn strip_fields(directive: &str) -> (String, Vec<(String, String)>) {
+    let Some(start) = directive.find('{') else {
+        return (directive.to_string(), Vec::new());
+    };
+    let Some(end) = directive[start..].find('}') else {
+        return (directive.to_string(), Vec::new());
+    };
+    let end = start + end;
+
+    let fields = directive[start + 1..end]
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let stripped = format!("{}{}", &directive[..start], &directive[end + 1..]);
+    (stripped, fields)
+}
+
+/// Splits `input` on top-level commas, i.e. ones that aren't inside a
+/// `{...}` field-predicate block
+fn split_top_level_directives(input: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0usize;
+    let mut start = 0usize;
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        for (i, ch) in input[start..].char_indices() {
+            match ch {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    let piece = &input[start..start + i];
+                    start += i + 1;
+                    return Some(piece);
+                }
+                _ => {}
+            }
+        }
+        done = true;
+        Some(&input[start..])
+    })
+}
+
+/// Strips any field predicates out of every directive in `input`,
+/// returning the bare directives plus the predicates that were attached
+#[cfg(feature = "kv")]
+fn split_fields_from_directives(input: &str) -> (Vec<String>, Vec<FieldDirective>) {
+    let mut field_directives = Vec::new();
+
+    let directives = split_top_level_directives(input)
+        .map(|directive| {
+            let (stripped, fields) = strip_fields(directive);
+            if !fields.is_empty()
+                && let Some((target, _)) = stripped.split_once('=')
+            {
+                field_directives.push(FieldDirective {
+                    target: target.trim().to_string(),
+                    fields,
+                });
+            }
+            stripped
+        })
+        .collect();
+
+    (directives, field_directives)
+}
+
+/// Strips any field predicates out of every directive in `input`,
+/// discarding them (nothing can check them without the `kv` feature)
+#[cfg(not(feature = "kv"))]
+fn split_fields_from_directives(input: &str) -> (Vec<String>, ()) {
+    let directives = split_top_level_directives(input)
+        .map(|directive| strip_fields(directive).0)
+        .collect();
+
+    (directives, ())
+}
+
+/// At or above this many directives, lookups switch from a linear scan of a
+/// `List` to a `Trie` descent
+const TRIE_THRESHOLD: usize = 15;
+
+/// Picks the cheapest `FiltersKind` representation for `mapping`
+fn kind_for(mut mapping: Vec<(Cow<'static, str>, log::LevelFilter)>) -> FiltersKind {
+    match mapping.len() {
+        d if d < TRIE_THRESHOLD => {
+            mapping.shrink_to_fit();
+            FiltersKind::List(mapping)
+        }
+        _ => {
+            let mut trie = Trie::default();
+            for (module, level) in mapping {
+                trie.insert(&module, level);
+            }
+            FiltersKind::Trie(trie)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum FiltersKind {
     /// A default filter (no logging)
     Default,
     /// A blanket filter (covers everything below it)
     Blanket,
-    /// A list of modules to level filters
-    ///     
-    /// This is split split off from the Map because its generally faster to
-    /// iterate over a small Vec compared to a HashMap
+    /// A list of modules to level filters, used below [`TRIE_THRESHOLD`]
     List(Vec<(Cow<'static, str>, log::LevelFilter)>),
-    /// A mapping of modules to level filters
-    ///
-    /// This is split split off from the Map because its generally faster to
-    /// iterate over a small Vec compared to a HashMap
-    Map(HashMap<Cow<'static, str>, log::LevelFilter>),
+    /// A trie of module path components to level filters, used at or
+    /// above [`TRIE_THRESHOLD`]
+    Trie(Trie),
+}
+
+/// A radix trie over `::`-separated module path components
+#[derive(Debug, Default)]
+pub struct Trie {
+    level: Option<log::LevelFilter>,
+    children: HashMap<Cow<'static, str>, Trie>,
+}
+
+impl Trie {
+    fn insert(&mut self, module: &str, level: log::LevelFilter) {
+        let mut node = self;
+        for component in module.split("::") {
+            node = node
+                .children
+                .entry(Cow::Owned(component.to_string()))
+                .or_default();
+        }
+        node.level = Some(level);
+    }
+
+    fn lookup(&self, module: &str) -> Option<log::LevelFilter> {
+        let mut node = self;
+        let mut found = None;
+        for component in module.split("::") {
+            let Some(next) = node.children.get(component) else {
+                break;
+            };
+            node = next;
+            if let Some(level) = node.level {
+                found = Some(level);
+            }
+        }
+        found
+    }
+
+    /// As [`lookup`](Self::lookup), but ignores `module`'s own exact node
+    /// and resolves the next less-specific ancestor instead
+    #[cfg(feature = "kv")]
+    fn lookup_excluding_exact(&self, module: &str) -> Option<log::LevelFilter> {
+        let components = module.split("::").collect::<Vec<_>>();
+        let mut node = self;
+        let mut found = None;
+        for (i, component) in components.iter().enumerate() {
+            let Some(next) = node.children.get(*component) else {
+                break;
+            };
+            node = next;
+            if i + 1 != components.len()
+                && let Some(level) = node.level
+            {
+                found = Some(level);
+            }
+        }
+        found
+    }
+
+    /// As [`lookup`](Self::lookup), but returns the matched prefix of
+    /// `module` itself rather than its level
+    #[cfg(feature = "kv")]
+    fn lookup_prefix<'a>(&self, module: &'a str) -> Option<&'a str> {
+        let mut node = self;
+        let mut found = None;
+        let mut offset = 0usize;
+        for component in module.split("::") {
+            let Some(next) = node.children.get(component) else {
+                break;
+            };
+            node = next;
+            offset += component.len();
+            if node.level.is_some() {
+                found = Some(&module[..offset]);
+            }
+            offset += "::".len();
+        }
+        found
+    }
+
+    /// Flattens every `(module, level)` entry in this trie back out into
+    /// `::`-joined module paths, for round-tripping into e.g. a [`Config`]
+    #[cfg(feature = "serde")]
+    fn entries(&self) -> Vec<(String, log::LevelFilter)> {
+        let mut out = Vec::new();
+        self.collect_entries(&mut String::new(), &mut out);
+        out
+    }
+
+    #[cfg(feature = "serde")]
+    fn collect_entries(&self, prefix: &mut String, out: &mut Vec<(String, log::LevelFilter)>) {
+        if let Some(level) = self.level {
+            out.push((prefix.clone(), level));
+        }
+        for (component, child) in &self.children {
+            let len = prefix.len();
+            if !prefix.is_empty() {
+                prefix.push_str("::");
+            }
+            prefix.push_str(component);
+            child.collect_entries(prefix, out);
+            prefix.truncate(len);
+        }
+    }
+}
+
+/// The reason a single directive failed to parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorReason {
+    /// The directive had a `=` but nothing before it (e.g. `=debug`)
+    EmptyTarget,
+    /// The directive had a `=` but the level after it wasn't valid
+    BadLevel,
+    /// The directive had no `=` and also wasn't a bare level (likely a
+    /// forgotten `=` between the target and the level)
+    MissingEquals,
+    /// The trailing `/regex` message filter wasn't a valid regex
+    BadPattern,
+}
+
+impl std::fmt::Display for ParseErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::EmptyTarget => "directive has an empty target",
+            Self::BadLevel => "directive has an invalid level",
+            Self::MissingEquals => "directive is missing a '=' between target and level",
+            Self::BadPattern => "trailing message filter is not a valid regex",
+        })
+    }
+}
+
+/// An error produced when a directive in a `RUST_LOG`-style string
+/// couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    directive: String,
+    offset: usize,
+    reason: ParseErrorReason,
+}
+
+impl ParseError {
+    /// The offending directive
+    pub fn directive(&self) -> &str {
+        &self.directive
+    }
+
+    /// The byte offset of the offending directive within the original input
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Why the directive was rejected
+    pub fn reason(&self) -> ParseErrorReason {
+        self.reason
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid directive '{}' at offset {}: {}",
+            self.directive, self.offset, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// An error produced by [`Builder::from_env`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromEnvError {
+    /// The environment variable wasn't set
+    NotPresent,
+    /// The environment variable was set, but wasn't valid
+    Invalid(ParseError),
+}
+
+impl std::fmt::Display for FromEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPresent => f.write_str("environment variable not set"),
+            Self::Invalid(err) => write!(f, "environment variable was invalid: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FromEnvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotPresent => None,
+            Self::Invalid(err) => Some(err),
+        }
+    }
+}
+
+/// Builds a [`Filters`] with error reporting, a configurable default
+/// directive and a configurable environment variable
+#[derive(Debug, Clone)]
+pub struct Builder {
+    default_directive: Option<log::LevelFilter>,
+    env_var: String,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            default_directive: None,
+            env_var: "RUST_LOG".to_string(),
+        }
+    }
+}
+
+impl Builder {
+    /// Creates a new builder with no default directive, reading from `RUST_LOG`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the level used as the `minimum` when the input has no bare
+    /// level token of its own
+    pub fn with_default_directive(mut self, level: log::LevelFilter) -> Self {
+        self.default_directive = Some(level);
+        self
+    }
+
+    /// Sets the environment variable [`Builder::from_env`] reads from
+    /// (defaults to `RUST_LOG`)
+    pub fn with_env_var(mut self, var: impl Into<String>) -> Self {
+        self.env_var = var.into();
+        self
+    }
+
+    /// Parses `input`, reporting the first malformed directive instead of
+    /// silently dropping it
+    pub fn parse(&self, input: &str) -> Result<Filters, ParseError> {
+        let (input, _pattern) = split_directives_and_message(input);
+
+        let mut mapping = Vec::new();
+        #[cfg(feature = "kv")]
+        let mut field_directives = Vec::new();
+        let mut minimum: Option<log::LevelFilter> = None;
+        let mut saw_bare_level = false;
+        let mut offset = 0;
+
+        for directive in split_top_level_directives(input) {
+            let trimmed = directive.trim();
+            let leading_space = directive.len() - directive.trim_start().len();
+
+            if !trimmed.is_empty() {
+                #[cfg(feature = "kv")]
+                let stripped = {
+                    let (stripped, fields) = strip_fields(trimmed);
+                    if !fields.is_empty()
+                        && let Some((target, _)) = stripped.split_once('=')
+                    {
+                        field_directives.push(FieldDirective {
+                            target: target.trim().to_string(),
+                            fields,
+                        });
+                    }
+                    stripped
+                };
+                #[cfg(not(feature = "kv"))]
+                let stripped = strip_fields(trimmed).0;
+
+                match parse_directive(&stripped) {
+                    Ok((Some(target), level)) => mapping.push((Cow::Owned(target), level)),
+                    Ok((None, level)) => {
+                        saw_bare_level = true;
+                        if level != log::LevelFilter::Off {
+                            minimum = Some(minimum.map_or(level, |m| m.max(level)));
+                        }
+                    }
+                    Err(reason) => {
+                        return Err(ParseError {
+                            directive: trimmed.to_string(),
+                            offset: offset + leading_space,
+                            reason,
+                        })
+                    }
+                }
+            }
+
+            offset += directive.len() + 1;
+        }
+
+        let minimum = if saw_bare_level {
+            minimum
+        } else {
+            self.default_directive
+        };
+
+        let kind = match mapping.len() {
+            0 if minimum.is_none() => FiltersKind::Default,
+            0 => FiltersKind::Blanket,
+            _ => kind_for(mapping),
+        };
+
+        #[cfg(feature = "regex")]
+        let message = match _pattern {
+            Some(pattern) => Some(Regex::new(pattern).map_err(|_| ParseError {
+                directive: pattern.to_string(),
+                offset: input.len() + 1,
+                reason: ParseErrorReason::BadPattern,
+            })?),
+            None => None,
+        };
+
+        Ok(Filters {
+            kind,
+            minimum,
+            #[cfg(feature = "regex")]
+            message,
+            #[cfg(feature = "kv")]
+            field_directives,
+        })
+    }
+
+    /// Parses the filters from this builder's environment variable
+    pub fn from_env(&self) -> Result<Filters, FromEnvError> {
+        match std::env::var(&self.env_var) {
+            Ok(input) => self.parse(&input).map_err(FromEnvError::Invalid),
+            Err(_) => Err(FromEnvError::NotPresent),
+        }
+    }
+}
+
+/// Parses a single directive, distinguishing *why* it failed instead of
+/// discarding the reason like [`parse`] does
+fn parse_directive(input: &str) -> Result<(Option<String>, log::LevelFilter), ParseErrorReason> {
+    match input.split_once('=') {
+        Some((target, level)) => {
+            if target.is_empty() {
+                return Err(ParseErrorReason::EmptyTarget);
+            }
+            let level = level
+                .to_ascii_uppercase()
+                .parse()
+                .map_err(|_| ParseErrorReason::BadLevel)?;
+            Ok((Some(target.to_string()), level))
+        }
+        None => match input.to_ascii_uppercase().parse() {
+            Ok(level) => Ok((None, level)),
+            Err(_) => Err(ParseErrorReason::MissingEquals),
+        },
+    }
 }
 
 /// Parsed level filters
@@ -26,6 +529,14 @@ pub struct Filters {
     pub kind: FiltersKind,
     /// The minimum level
     pub minimum: Option<log::LevelFilter>,
+    /// An optional regex the log message must match, from a trailing
+    /// `/regex` suffix on the input
+    #[cfg(feature = "regex")]
+    message: Option<Regex>,
+    /// `target{key=value,...}` predicates that a record's structured
+    /// fields must satisfy, keyed by the directive's target
+    #[cfg(feature = "kv")]
+    field_directives: Vec<FieldDirective>,
 }
 
 impl Default for Filters {
@@ -33,6 +544,10 @@ impl Default for Filters {
         Self {
             kind: FiltersKind::Default,
             minimum: None,
+            #[cfg(feature = "regex")]
+            message: None,
+            #[cfg(feature = "kv")]
+            field_directives: Vec::new(),
         }
     }
 }
@@ -41,10 +556,14 @@ impl Filters {
     #[allow(clippy::should_implement_trait)]
     /// Parses the level filters from the input str
     pub fn from_str(input: &str) -> Self {
-        let mut mapping = input.split(',').filter_map(parse).collect::<Vec<_>>();
+        let (input, _pattern) = split_directives_and_message(input);
+        #[cfg_attr(not(feature = "kv"), allow(unused_variables))]
+        let (directives, field_directives) = split_fields_from_directives(input);
+
+        let mapping = directives.iter().filter_map(|d| parse(d)).collect::<Vec<_>>();
 
-        let minimum = input
-            .split(',')
+        let minimum = directives
+            .iter()
             .filter(|s| !s.contains('='))
             .flat_map(|s| s.parse().ok())
             .filter(|&l| l != log::LevelFilter::Off)
@@ -53,14 +572,17 @@ impl Filters {
         let kind = match mapping.len() {
             0 if minimum.is_none() => FiltersKind::Default,
             0 => FiltersKind::Blanket,
-            d if d < 15 => {
-                mapping.shrink_to_fit();
-                FiltersKind::List(mapping)
-            }
-            _ => FiltersKind::Map(mapping.into_iter().collect()),
+            _ => kind_for(mapping),
         };
 
-        Self { kind, minimum }
+        Self {
+            kind,
+            minimum,
+            #[cfg(feature = "regex")]
+            message: _pattern.and_then(|pattern| Regex::new(pattern).ok()),
+            #[cfg(feature = "kv")]
+            field_directives,
+        }
     }
 
     /// Parses the level filters from the environment variable `RUST_LOG`
@@ -72,6 +594,44 @@ impl Filters {
             .unwrap_or_default()
     }
 
+    /// Creates a [`Builder`] for configuring a default directive, a
+    /// custom environment variable, and error-reporting parsing
+    pub fn builder() -> Builder {
+        Builder::new()
+    }
+
+    /// Parses a structured [`Config`] (JSON or YAML) into `Filters`
+    #[cfg(feature = "serde")]
+    pub fn from_config_str(input: &str, format: ConfigFormat) -> Result<Self, FromConfigError> {
+        let config: Config = match format {
+            ConfigFormat::Json => {
+                serde_json::from_str(input).map_err(|err| FromConfigError::Format(err.to_string()))?
+            }
+            ConfigFormat::Yaml => {
+                serde_yaml::from_str(input).map_err(|err| FromConfigError::Format(err.to_string()))?
+            }
+        };
+        config.build().map_err(FromConfigError::Config)
+    }
+
+    /// Parses a JSON-encoded [`Config`] into `Filters`
+    #[cfg(feature = "serde")]
+    pub fn from_json_str(input: &str) -> Result<Self, FromConfigError> {
+        Self::from_config_str(input, ConfigFormat::Json)
+    }
+
+    /// Parses a YAML-encoded [`Config`] into `Filters`
+    #[cfg(feature = "serde")]
+    pub fn from_yaml_str(input: &str) -> Result<Self, FromConfigError> {
+        Self::from_config_str(input, ConfigFormat::Yaml)
+    }
+
+    /// Produces the [`Config`] this `Filters` would round-trip back to
+    #[cfg(feature = "serde")]
+    pub fn to_config(&self) -> Config {
+        Config::from(self)
+    }
+
     #[inline]
     /// Checks to see whether the module in the metadata has logging enabled
     pub fn is_enabled(&self, metadata: &log::Metadata<'_>) -> bool {
@@ -81,29 +641,128 @@ impl Filters {
         }
     }
 
+    #[inline]
+    /// Checks to see whether `record` is enabled, matching both its level
+    /// and, if present, the trailing `/regex` message filter
+    pub fn matches(&self, record: &log::Record<'_>) -> bool {
+        let enabled = match self.find_module(record.target()) {
+            Some(level) => record.level() <= level,
+            None => false,
+        };
+
+        #[cfg(feature = "kv")]
+        let enabled = {
+            let mut enabled = enabled;
+            if enabled
+                && let Some(governing) = self.governing_target(record.target())
+                && let Some(directive) = self.find_field_directive(governing)
+                && !directive.matches(record)
+            {
+                // the directive's fields didn't match this record, so it
+                // doesn't apply here; fall back to the next less-specific
+                // directive (or the blanket minimum) rather than this (more
+                // specific) directive's level
+                enabled = self
+                    .find_module_excluding_exact(governing)
+                    .is_some_and(|level| record.level() <= level);
+            }
+            enabled
+        };
+
+        if !enabled {
+            return false;
+        }
+
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = &self.message {
+            return pattern.is_match(&record.args().to_string());
+        }
+
+        true
+    }
+
+    #[cfg(feature = "kv")]
+    #[inline]
+    fn find_field_directive(&self, target: &str) -> Option<&FieldDirective> {
+        self.field_directives
+            .iter()
+            .find(|directive| directive.target == target)
+    }
+
+    /// Resolves `module` to the target that actually governs its level,
+    /// i.e. the most specific registered entry that is `module` itself or
+    /// one of its `::`-separated ancestors (mirroring [`find_module`]'s
+    /// own resolution), so field predicates registered on an ancestor
+    /// still apply to its submodules
+    #[cfg(feature = "kv")]
+    #[inline]
+    fn governing_target<'a>(&self, module: &'a str) -> Option<&'a str> {
+        match &self.kind {
+            FiltersKind::Default | FiltersKind::Blanket => None,
+            FiltersKind::Trie(trie) => trie.lookup_prefix(module),
+            FiltersKind::List(_) => {
+                if self.find_exact(module).is_some() {
+                    return Some(module);
+                }
+
+                let mut last = false;
+                for (i, ch) in module.char_indices().rev() {
+                    if last {
+                        last = false;
+                        if ch == ':' && self.find_exact(&module[..i]).is_some() {
+                            return Some(&module[..i]);
+                        }
+                    } else if ch == ':' {
+                        last = true
+                    }
+                }
+
+                None
+            }
+        }
+    }
+
     #[inline]
     /// Attempts to find the specified `module` in this collection
     ///
     /// If the `FiltersKind` is `Default`, then None is returned.
     pub fn find_module(&self, module: &str) -> Option<log::LevelFilter> {
-        match self.kind {
+        match &self.kind {
             FiltersKind::Default => return None,
             FiltersKind::Blanket => return self.minimum,
+            FiltersKind::Trie(trie) => return trie.lookup(module).or(self.minimum),
             _ => {}
         }
 
-        if let Some(level) = self.find_exact(module) {
-            return Some(level);
+        self.find_exact(module).or_else(|| self.find_prefix(module))
+    }
+
+    /// As [`find_module`](Self::find_module), but ignores `module`'s own
+    /// exact entry and resolves the next less-specific one instead
+    #[cfg(feature = "kv")]
+    #[inline]
+    fn find_module_excluding_exact(&self, module: &str) -> Option<log::LevelFilter> {
+        match &self.kind {
+            FiltersKind::Default => None,
+            FiltersKind::Blanket => self.minimum,
+            FiltersKind::Trie(trie) => trie.lookup_excluding_exact(module).or(self.minimum),
+            _ => self.find_prefix(module),
         }
+    }
 
+    /// Walks `module`'s `::`-separated prefixes from the most to least
+    /// specific, returning the first one with an exact entry, or the
+    /// blanket minimum if none match
+    #[inline]
+    fn find_prefix(&self, module: &str) -> Option<log::LevelFilter> {
         let mut last = false;
         for (i, ch) in module.char_indices().rev() {
             if last {
                 last = false;
-                if ch == ':' {
-                    if let Some(level) = self.find_exact(&module[..i]) {
-                        return Some(level);
-                    }
+                if ch == ':'
+                    && let Some(level) = self.find_exact(&module[..i])
+                {
+                    return Some(level);
                 }
             } else if ch == ':' {
                 last = true
@@ -115,16 +774,15 @@ impl Filters {
 
     #[inline]
     fn find_exact(&self, module: &str) -> Option<log::LevelFilter> {
-        match &self.kind {
-            FiltersKind::Default => None,
-            FiltersKind::Blanket => self.minimum,
-            FiltersKind::List(levels) => {
-                levels
-                    .iter()
-                    .find_map(|(m, level)| if m == module { Some(*level) } else { None })
-            }
-            FiltersKind::Map(levels) => levels.get(module).copied(),
-        }
+        // only ever called with a `List` kind: `find_module` and
+        // `find_module_excluding_exact` both handle `Default`/`Blanket`/
+        // `Trie` themselves before reaching here
+        let FiltersKind::List(levels) = &self.kind else {
+            return None;
+        };
+        levels
+            .iter()
+            .find_map(|(m, level)| if m == module { Some(*level) } else { None })
     }
 }
 
@@ -137,6 +795,228 @@ fn parse(input: &str) -> Option<(Cow<'static, str>, log::LevelFilter)> {
     ))
 }
 
+/// A JSON/YAML-friendly configuration for [`Filters`], as a top-level
+/// `minimum` level plus a `module -> level` map
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Config {
+    /// The blanket minimum level, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<String>,
+    /// Per-module level overrides
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub modules: HashMap<String, String>,
+}
+
+/// An error produced when a [`Config`] has an invalid level name
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    target: String,
+    level: String,
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid level '{}' for '{}'", self.level, self.target)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for ConfigError {}
+
+/// The structured format a [`Config`] is encoded in
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// JSON
+    Json,
+    /// YAML
+    Yaml,
+}
+
+/// An error produced by [`Filters::from_config_str`] and friends
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum FromConfigError {
+    /// The input wasn't valid JSON/YAML, or didn't match [`Config`]'s shape
+    Format(String),
+    /// The config parsed, but had an invalid level name
+    Config(ConfigError),
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for FromConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format(err) => write!(f, "invalid config: {err}"),
+            Self::Config(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for FromConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Format(_) => None,
+            Self::Config(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Config {
+    /// Builds the [`Filters`] this config describes, reporting the first
+    /// module (or `"minimum"`) with an invalid level name
+    pub fn build(&self) -> Result<Filters, ConfigError> {
+        let minimum = self
+            .minimum
+            .as_deref()
+            .map(|level| {
+                level
+                    .to_ascii_uppercase()
+                    .parse()
+                    .map_err(|_| ConfigError {
+                        target: "minimum".to_string(),
+                        level: level.to_string(),
+                    })
+            })
+            .transpose()?;
+
+        let mapping = self
+            .modules
+            .iter()
+            .map(|(module, level)| {
+                let level = level
+                    .to_ascii_uppercase()
+                    .parse()
+                    .map_err(|_| ConfigError {
+                        target: module.clone(),
+                        level: level.clone(),
+                    })?;
+                Ok((Cow::Owned(module.clone()), level))
+            })
+            .collect::<Result<Vec<_>, ConfigError>>()?;
+
+        let kind = match mapping.len() {
+            0 if minimum.is_none() => FiltersKind::Default,
+            0 => FiltersKind::Blanket,
+            _ => kind_for(mapping),
+        };
+
+        Ok(Filters {
+            kind,
+            minimum,
+            #[cfg(feature = "regex")]
+            message: None,
+            #[cfg(feature = "kv")]
+            field_directives: Vec::new(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<&Filters> for Config {
+    fn from(filters: &Filters) -> Self {
+        let modules = match &filters.kind {
+            FiltersKind::Default | FiltersKind::Blanket => HashMap::new(),
+            FiltersKind::List(entries) => entries
+                .iter()
+                .map(|(module, level)| (module.to_string(), level.to_string()))
+                .collect(),
+            FiltersKind::Trie(trie) => trie
+                .entries()
+                .into_iter()
+                .map(|(module, level)| (module, level.to_string()))
+                .collect(),
+        };
+
+        Self {
+            minimum: filters.minimum.map(|level| level.to_string()),
+            modules,
+        }
+    }
+}
+
+/// A [`Filters`] that can be swapped out at runtime
+#[derive(Debug)]
+pub struct ReloadableFilters {
+    current: std::sync::Arc<std::sync::RwLock<std::sync::Arc<Filters>>>,
+}
+
+impl ReloadableFilters {
+    /// Wraps `filters` for runtime reloading
+    pub fn new(filters: Filters) -> Self {
+        Self {
+            current: std::sync::Arc::new(std::sync::RwLock::new(std::sync::Arc::new(filters))),
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    /// Parses the level filters from the input str
+    pub fn from_str(input: &str) -> Self {
+        Self::new(Filters::from_str(input))
+    }
+
+    /// Parses the level filters from the environment variable `RUST_LOG`
+    pub fn from_env() -> Self {
+        Self::new(Filters::from_env())
+    }
+
+    /// Creates a cheaply-cloneable [`Handle`] onto these filters
+    pub fn handle(&self) -> Handle {
+        Handle {
+            current: std::sync::Arc::clone(&self.current),
+        }
+    }
+}
+
+impl Default for ReloadableFilters {
+    fn default() -> Self {
+        Self::new(Filters::default())
+    }
+}
+
+/// A cheaply-cloneable handle onto a [`ReloadableFilters`]
+#[derive(Debug, Clone)]
+pub struct Handle {
+    current: std::sync::Arc<std::sync::RwLock<std::sync::Arc<Filters>>>,
+}
+
+impl Handle {
+    #[inline]
+    /// Checks to see whether the module in the metadata has logging
+    /// enabled, against the current snapshot
+    pub fn is_enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.snapshot().is_enabled(metadata)
+    }
+
+    #[inline]
+    /// Checks to see whether `record` is enabled, against the current
+    /// snapshot
+    pub fn matches(&self, record: &log::Record<'_>) -> bool {
+        self.snapshot().matches(record)
+    }
+
+    /// Atomically swaps in `filters`; subsequent `is_enabled`/`matches`
+    /// calls on any clone of this handle observe it immediately
+    pub fn reload(&self, filters: Filters) {
+        *self.current.write().unwrap() = std::sync::Arc::new(filters);
+    }
+
+    /// Parses `input` and atomically swaps in the result
+    pub fn reload_from_str(&self, input: &str) {
+        self.reload(Filters::from_str(input));
+    }
+
+    #[inline]
+    fn snapshot(&self) -> std::sync::Arc<Filters> {
+        std::sync::Arc::clone(&self.current.read().unwrap())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,6 +1040,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn trie_above_threshold() {
+        let input = (0..TRIE_THRESHOLD + 1)
+            .map(|i| format!("mod{i}=debug"))
+            .chain(std::iter::once("mod3::sub=trace".to_string()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let filters = Filters::from_str(&input);
+        assert!(matches!(filters.kind, FiltersKind::Trie(_)));
+
+        assert_eq!(filters.find_module("mod3").unwrap(), log::LevelFilter::Debug);
+        assert_eq!(
+            filters.find_module("mod3::sub").unwrap(),
+            log::LevelFilter::Trace
+        );
+        assert_eq!(
+            filters.find_module("mod3::sub::deeper").unwrap(),
+            log::LevelFilter::Trace
+        );
+        assert!(filters.find_module("unknown").is_none());
+    }
+
     #[test]
     fn minimum() {
         let filters =
@@ -180,4 +1082,296 @@ mod tests {
             assert_eq!(filters.find_module(module).unwrap(), *expected);
         }
     }
+
+    #[test]
+    fn builder_reports_bad_directive() {
+        let err = Filters::builder()
+            .parse("debug,foo=nope,baz=info")
+            .unwrap_err();
+
+        assert_eq!(err.directive(), "foo=nope");
+        assert_eq!(err.offset(), 6);
+        assert_eq!(err.reason(), ParseErrorReason::BadLevel);
+    }
+
+    #[test]
+    fn builder_reports_missing_equals() {
+        let err = Filters::builder().parse("foo:bar").unwrap_err();
+        assert_eq!(err.reason(), ParseErrorReason::MissingEquals);
+    }
+
+    #[test]
+    fn builder_reports_empty_target() {
+        let err = Filters::builder().parse("=debug").unwrap_err();
+        assert_eq!(err.reason(), ParseErrorReason::EmptyTarget);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn builder_reports_bad_pattern() {
+        let err = Filters::builder().parse("info/(").unwrap_err();
+        assert_eq!(err.reason(), ParseErrorReason::BadPattern);
+    }
+
+    #[test]
+    fn builder_default_directive() {
+        let filters = Filters::builder()
+            .with_default_directive(log::LevelFilter::Warn)
+            .parse("foo=debug")
+            .unwrap();
+
+        assert_eq!(
+            filters.find_module("something").unwrap(),
+            log::LevelFilter::Warn
+        );
+        assert_eq!(
+            filters.find_module("foo").unwrap(),
+            log::LevelFilter::Debug
+        );
+    }
+
+    #[test]
+    fn builder_default_directive_not_max_with_bare_token() {
+        let filters = Filters::builder()
+            .with_default_directive(log::LevelFilter::Info)
+            .parse("warn,foo=trace")
+            .unwrap();
+
+        assert_eq!(
+            filters.find_module("bar").unwrap(),
+            log::LevelFilter::Warn
+        );
+        assert_eq!(
+            filters.find_module("foo").unwrap(),
+            log::LevelFilter::Trace
+        );
+    }
+
+    #[test]
+    fn builder_from_env() {
+        let var = "LOG_FILTER_PARSE_TEST_BUILDER_FROM_ENV";
+        // SAFETY: test is single-threaded with respect to this env var
+        unsafe { std::env::remove_var(var) };
+        assert_eq!(
+            Filters::builder().with_env_var(var).from_env().unwrap_err(),
+            FromEnvError::NotPresent
+        );
+
+        // SAFETY: test is single-threaded with respect to this env var
+        unsafe { std::env::set_var(var, "debug,foo=nope") };
+        assert!(matches!(
+            Filters::builder().with_env_var(var).from_env().unwrap_err(),
+            FromEnvError::Invalid(_)
+        ));
+        // SAFETY: test is single-threaded with respect to this env var
+        unsafe { std::env::remove_var(var) };
+    }
+
+    #[test]
+    fn reload_swaps_in_new_directives() {
+        let reloadable = ReloadableFilters::from_str("foo=info");
+        let handle = reloadable.handle();
+
+        let metadata = log::MetadataBuilder::new()
+            .target("foo")
+            .level(log::Level::Debug)
+            .build();
+        assert!(!handle.is_enabled(&metadata));
+
+        handle.reload_from_str("foo=debug");
+        assert!(handle.is_enabled(&metadata));
+
+        // a handle cloned after the reload observes the same snapshot
+        assert!(handle.clone().is_enabled(&metadata));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_from_json_matches_equivalent_directive_string() {
+        let json = r#"{"minimum":"debug","modules":{"foo::bar":"off","foo":"Info"}}"#;
+        let from_json = Filters::from_json_str(json).unwrap();
+        let from_str = Filters::from_str("debug,foo::bar=off,foo=info");
+
+        for module in ["foo::bar", "foo", "something"] {
+            assert_eq!(
+                from_json.find_module(module),
+                from_str.find_module(module)
+            );
+        }
+        assert!(matches!(from_json.kind, FiltersKind::List(_)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_rejects_bad_level() {
+        let json = r#"{"modules":{"foo":"nope"}}"#;
+        let err = Filters::from_json_str(json).unwrap_err();
+        assert!(matches!(err, FromConfigError::Config(_)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_round_trips_through_yaml() {
+        let filters = Filters::from_str("warn,foo::bar=off,foo::baz=trace");
+        let yaml = serde_yaml::to_string(&filters.to_config()).unwrap();
+        let reparsed = Filters::from_yaml_str(&yaml).unwrap();
+
+        for module in ["foo::bar", "foo::baz", "something"] {
+            assert_eq!(filters.find_module(module), reparsed.find_module(module));
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn message_pattern() {
+        let filters = Filters::from_str("info,foo=debug/refusing to .*connect");
+
+        macro_rules! record {
+            ($target:expr, $level:expr, $($args:tt)*) => {
+                log::Record::builder()
+                    .target($target)
+                    .level($level)
+                    .args(format_args!($($args)*))
+                    .build()
+            };
+        }
+
+        let matching = record!("foo", log::Level::Debug, "refusing to reconnect");
+        assert!(filters.matches(&matching));
+
+        let wrong_message = record!("foo", log::Level::Debug, "connected ok");
+        assert!(!filters.matches(&wrong_message));
+
+        let wrong_level = record!("foo", log::Level::Trace, "refusing to reconnect");
+        assert!(!filters.matches(&wrong_level));
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn field_predicates() {
+        // `foo` only gets its verbose `debug` level when its fields match;
+        // otherwise it falls back to the blanket `warn` minimum
+        let filters = Filters::from_str("warn,foo{user_id=42,retry=true}=debug");
+
+        macro_rules! record {
+            ($level:expr, $kvs:expr) => {
+                log::Record::builder()
+                    .target("foo")
+                    .level($level)
+                    .key_values($kvs)
+                    .build()
+            };
+        }
+
+        let kvs = [
+            ("user_id", log::kv::Value::from(42i64)),
+            ("retry", log::kv::Value::from(true)),
+        ];
+        let matching = record!(log::Level::Debug, &kvs);
+        assert!(filters.matches(&matching));
+
+        let kvs = [
+            ("user_id", log::kv::Value::from(7i64)),
+            ("retry", log::kv::Value::from(true)),
+        ];
+        let wrong_value = record!(log::Level::Debug, &kvs);
+        assert!(!filters.matches(&wrong_value));
+
+        // fields don't match, but this still clears the blanket `warn` minimum
+        let wrong_value_at_minimum = record!(log::Level::Warn, &kvs);
+        assert!(filters.matches(&wrong_value_at_minimum));
+
+        let kvs = [("user_id", log::kv::Value::from(42i64))];
+        let missing_field = record!(log::Level::Debug, &kvs);
+        assert!(!filters.matches(&missing_field));
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn field_predicate_falls_back_to_less_specific_directive() {
+        // when `foo::bar`'s fields don't match, the less-specific `foo`
+        // directive should govern instead of jumping straight to minimum
+        let filters = Filters::from_str("foo=debug,foo::bar{user_id=1}=trace");
+
+        let kvs = [("user_id", log::kv::Value::from(2i64))];
+        let record = log::Record::builder()
+            .target("foo::bar")
+            .level(log::Level::Debug)
+            .key_values(&kvs)
+            .build();
+
+        assert!(filters.matches(&record));
+    }
+
+    #[cfg(all(feature = "kv", feature = "regex"))]
+    #[test]
+    fn field_predicate_fallback_still_checks_message_pattern() {
+        // falling back past a field directive that didn't match should
+        // still run the trailing message-pattern check, not short-circuit
+        let filters = Filters::from_str("warn,foo{user_id=1}=debug/only this text");
+
+        let kvs = [("user_id", log::kv::Value::from(2i64))];
+        let record = log::Record::builder()
+            .target("foo")
+            .level(log::Level::Warn)
+            .key_values(&kvs)
+            .args(format_args!("completely unrelated message"))
+            .build();
+
+        assert!(!filters.matches(&record));
+    }
+
+    #[test]
+    fn slash_inside_field_value_is_not_a_message_separator() {
+        // a `/` in a field value (e.g. a path) must not be mistaken for
+        // the trailing `/regex` message separator
+        let filters = Filters::from_str("foo{path=a/b}=debug");
+        assert_eq!(filters.find_module("foo"), Some(log::LevelFilter::Debug));
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn field_predicate_gates_nested_submodules_above_trie_threshold() {
+        // same as `field_predicate_gates_nested_submodules_too`, but with
+        // enough directives to force the `Trie` kind, exercising
+        // `Trie::lookup_prefix` instead of the `List` walk
+        let input = (0..TRIE_THRESHOLD)
+            .map(|i| format!("mod{i}=info"))
+            .chain(std::iter::once("foo{user_id=1}=debug".to_string()))
+            .collect::<Vec<_>>()
+            .join(",");
+        let filters = Filters::builder()
+            .with_default_directive(log::LevelFilter::Warn)
+            .parse(&input)
+            .unwrap();
+        assert!(matches!(filters.kind, FiltersKind::Trie(_)));
+
+        let kvs = [("user_id", log::kv::Value::from(2i64))];
+        let record = log::Record::builder()
+            .target("foo::bar")
+            .level(log::Level::Debug)
+            .key_values(&kvs)
+            .build();
+
+        assert!(!filters.matches(&record));
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn field_predicate_gates_nested_submodules_too() {
+        // `foo{user_id=1}=debug` should gate `foo`'s submodules as well,
+        // since they inherit `foo`'s level through the normal prefix match
+        let filters = Filters::from_str("warn,foo{user_id=1}=debug");
+
+        let kvs = [("user_id", log::kv::Value::from(2i64))];
+        let record = log::Record::builder()
+            .target("foo::bar")
+            .level(log::Level::Debug)
+            .key_values(&kvs)
+            .build();
+
+        // fields don't match, so `foo::bar` falls back to the blanket
+        // `warn` minimum rather than inheriting `foo`'s ungated `debug`
+        assert!(!filters.matches(&record));
+    }
 }